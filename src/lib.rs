@@ -1,16 +1,185 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 use wasm_bindgen::prelude::*;
-use serde::{Serialize, Deserialize};
+use serde::{Serialize, Deserialize, Deserializer, Serializer};
 
 // Use wee_alloc for smaller WASM binary size (optional but good practice)
 #[cfg(feature = "wee_alloc")]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+/// The default number of decimal places assumed for a bare integer amount
+/// (e.g. one that arrives from JS as `10050` rather than `"100.50"`).
+const DEFAULT_SCALE: u32 = 2;
+
+/// The default currency assumed when a value doesn't carry one of its own.
+const DEFAULT_CURRENCY: &str = "USD";
+
+/// The contra-account a chargeback's reversing entry is booked against, so
+/// the funds removed from a client's account stay visible in the trial
+/// balance instead of vanishing from `balances` while `AccountState.total`
+/// drops. It is an ordinary account id: nothing reserves it, so a CSV
+/// `client` or a transaction entry using the same id shares this account.
+const CHARGEBACK_CONTRA_ACCOUNT: &str = "Chargebacks";
+
+/// A fixed-point monetary amount: an integer count of minor units (e.g. cents)
+/// plus a decimal scale and a currency tag. Storing money this way keeps
+/// arithmetic exact (no floating point rounding) and makes the
+/// cents-vs-dollars boundary explicit at the JS/WASM edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Money {
+    minor_units: i64,
+    scale: u32,
+    currency: [u8; 3],
+}
+
+impl Money {
+    pub fn new(minor_units: i64, scale: u32, currency: &str) -> Result<Money, String> {
+        let code = currency.as_bytes();
+        if code.len() != 3 {
+            return Err(format!("Invalid currency code '{}': expected a 3-letter code.", currency));
+        }
+        let mut currency = [0u8; 3];
+        currency.copy_from_slice(code);
+        currency.make_ascii_uppercase();
+        Ok(Money { minor_units, scale, currency })
+    }
+
+    pub fn zero() -> Money {
+        Money { minor_units: 0, scale: DEFAULT_SCALE, currency: *b"USD" }
+    }
+
+    pub fn minor_units(&self) -> i64 {
+        self.minor_units
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn currency(&self) -> &str {
+        std::str::from_utf8(&self.currency).unwrap_or(DEFAULT_CURRENCY)
+    }
+
+    /// Adds two amounts, rejecting mixed currencies instead of silently
+    /// summing incompatible units. Amounts at different scales are upscaled
+    /// to the finer (larger) of the two before adding.
+    pub fn checked_add(&self, other: &Money) -> Result<Money, String> {
+        if self.currency() != other.currency() {
+            return Err(format!(
+                "Cannot add mismatched currencies: {} and {}.",
+                self.currency(), other.currency()
+            ));
+        }
+
+        let scale = self.scale.max(other.scale);
+        let lhs = Money::rescale_minor_units(self.minor_units, self.scale, scale)?;
+        let rhs = Money::rescale_minor_units(other.minor_units, other.scale, scale)?;
+        let minor_units = lhs.checked_add(rhs)
+            .ok_or_else(|| "Money amount overflowed.".to_string())?;
+
+        Money::new(minor_units, scale, self.currency())
+    }
+
+    /// Scales `minor_units` up from `from_scale` to `to_scale`, erroring
+    /// instead of overflowing/wrapping when the scale gap is too large for
+    /// `i64` to represent (e.g. a bogus amount carrying a huge scale).
+    fn rescale_minor_units(minor_units: i64, from_scale: u32, to_scale: u32) -> Result<i64, String> {
+        let factor = 10i64.checked_pow(to_scale - from_scale)
+            .ok_or_else(|| "Money scale difference is too large to represent.".to_string())?;
+        minor_units.checked_mul(factor)
+            .ok_or_else(|| "Money amount overflowed while rescaling.".to_string())
+    }
+
+    /// Returns the additive inverse of this amount, keeping its scale and currency.
+    pub fn negated(&self) -> Money {
+        Money { minor_units: -self.minor_units, scale: self.scale, currency: self.currency }
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = self.scale as usize;
+        let sign = if self.minor_units < 0 { "-" } else { "" };
+        let magnitude = self.minor_units.unsigned_abs();
+
+        if scale == 0 {
+            return write!(f, "{}{}", sign, magnitude);
+        }
+
+        let digits = format!("{:0>width$}", magnitude, width = scale + 1);
+        let split = digits.len() - scale;
+        write!(f, "{}{}.{}", sign, &digits[..split], &digits[split..])
+    }
+}
+
+impl FromStr for Money {
+    type Err = String;
+
+    /// Parses a decimal string like `"100.00"` or `"-42"` into a `Money` with
+    /// `USD` currency, using the number of fractional digits present as the
+    /// scale (so `"1.5"` round-trips as 1 decimal place, not 2).
+    fn from_str(s: &str) -> Result<Money, String> {
+        let s = s.trim();
+        let negative = s.starts_with('-');
+        let unsigned = s.strip_prefix('-').unwrap_or(s);
+
+        let (whole, frac) = match unsigned.split_once('.') {
+            Some((whole, frac)) => (whole, frac),
+            None => (unsigned, ""),
+        };
+
+        if whole.is_empty() && frac.is_empty() {
+            return Err(format!("Invalid money amount '{}'.", s));
+        }
+        if !whole.chars().all(|c| c.is_ascii_digit()) || !frac.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("Invalid money amount '{}'.", s));
+        }
+
+        let scale = frac.len() as u32;
+        let digits = format!("{}{}", if whole.is_empty() { "0" } else { whole }, frac);
+        let mut minor_units: i64 = digits.parse()
+            .map_err(|_| format!("Invalid money amount '{}'.", s))?;
+        if negative {
+            minor_units = -minor_units;
+        }
+
+        Money::new(minor_units, scale, DEFAULT_CURRENCY)
+    }
+}
+
+/// JSON can hand us money either as a decimal string (`"100.00"`) or as a
+/// bare integer of minor units at the default scale/currency.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MoneyRepr {
+    Decimal(String),
+    MinorUnits(i64),
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        match MoneyRepr::deserialize(deserializer)? {
+            MoneyRepr::Decimal(s) => s.parse::<Money>().map_err(serde::de::Error::custom),
+            MoneyRepr::MinorUnits(minor_units) => {
+                Money::new(minor_units, DEFAULT_SCALE, DEFAULT_CURRENCY).map_err(serde::de::Error::custom)
+            }
+        }
+    }
+}
+
 /// Represents a single side of a transaction (Debit or Credit).
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Entry {
     pub account_id: String,
-    pub amount: i64, // Positive = Credit, Negative = Debit
+    pub amount: Money, // Positive = Credit, Negative = Debit
 }
 
 /// A Financial Transaction consisting of multiple entries.
@@ -28,41 +197,105 @@ impl Transaction {
     /// key validation logic: Returns an Result.
     /// If sum != 0, it rejects the creation.
     pub fn new(id: u32, description: String, timestamp: u64, entries: Vec<Entry>) -> Result<Transaction, String> {
-        let balance: i64 = entries.iter().map(|e| e.amount).sum();
-        
-        if balance != 0 {
-            return Err(format!("Transaction unbalanced: Sum is {}. Must be 0.", balance));
-        }
-
         if entries.is_empty() {
              return Err("Transaction cannot be empty.".to_string());
         }
 
+        let mut balance = entries[0].amount;
+        for entry in &entries[1..] {
+            balance = balance.checked_add(&entry.amount)
+                .map_err(|e| format!("Transaction unbalanced: {}", e))?;
+        }
+
+        if balance.minor_units() != 0 {
+            return Err(format!("Transaction unbalanced: Sum is {}. Must be 0.", balance));
+        }
+
         Ok(Transaction {
             id,
             description,
             timestamp,
             entries,
-            category: None, 
+            category: None,
         })
     }
 }
 
+/// A single account's net position, as reported to JS by `list_accounts`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AccountBalance {
+    pub account_id: String,
+    pub balance: i64,
+}
+
+/// An account's dispute-aware state, as reported to JS by `get_account_state`.
+/// `total` always equals `available + held`; `locked` accounts (set by a
+/// chargeback) no longer accept new entries.
+///
+/// `available`/`held`/`total` always share one implicit decimal `scale` (not
+/// reported to JS, since `get_balance` reports raw minor units too) so that,
+/// like `Engine::balances`, this stays reconciled across entries posted at
+/// different scales instead of silently summing mismatched minor units.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AccountState {
+    pub available: i64,
+    pub held: i64,
+    pub total: i64,
+    pub locked: bool,
+    #[serde(skip)]
+    scale: u32,
+}
+
+/// Tracks whether a (transaction id, account) pair currently has an open
+/// dispute, so a repeated `resolve`/`chargeback` or a dispute on an already
+/// charged-back pair can be ignored instead of erroring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DisputeStatus {
+    Disputed,
+    ChargedBack,
+}
+
+/// The action carried by a `dispute`/`resolve`/`chargeback` CSV row.
+#[derive(Clone, Copy, Debug)]
+enum DisputeKind {
+    Dispute,
+    Resolve,
+    Chargeback,
+}
+
+/// One element of the array passed to `add_batch_val`: the same shape as the
+/// individual arguments `add_transaction_val` takes, bundled into a JSON object.
+#[derive(Deserialize)]
+struct TransactionInput {
+    id: u32,
+    description: String,
+    timestamp: u64,
+    entries: Vec<Entry>,
+}
+
 #[wasm_bindgen]
 pub struct Engine {
     transactions: Vec<Transaction>,
+    balances: HashMap<String, Money>,
+    accounts: HashMap<String, AccountState>,
+    disputes: HashMap<(u32, String), DisputeStatus>,
 }
 
 #[wasm_bindgen]
 impl Engine {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Engine {
-        
+
         // Hook up panic handler for better debugging in browser console
         #[cfg(feature = "console_error_panic_hook")]
         console_error_panic_hook::set_once();
-        
-        Engine { transactions: Vec::new() }
+
+        Engine {
+            transactions: Vec::new(),
+            balances: HashMap::new(),
+            accounts: HashMap::new(),
+            disputes: HashMap::new(),
+        }
     }
 
     /// Adds a transaction to the ledger.
@@ -76,6 +309,12 @@ impl Engine {
 
         match Transaction::new(id, description, timestamp, entries) {
             Ok(tx) => {
+                if let Some(locked) = self.locked_account(&tx.entries) {
+                    return format!("Error: Account '{}' is locked.", locked);
+                }
+                if let Err(e) = self.apply_balances(&tx) {
+                    return format!("Error: {}", e);
+                }
                 self.transactions.push(tx);
                 "Success: Transaction Committed".to_string()
             },
@@ -83,9 +322,383 @@ impl Engine {
         }
     }
 
+    /// Commits a batch of transactions atomically: every transaction is
+    /// validated (and checked against locked accounts) before any of them are
+    /// applied, so a single bad entry rolls back the whole batch instead of
+    /// leaving it partially committed.
+    pub fn add_batch_val(&mut self, js_batch: JsValue) -> String {
+        let inputs: Vec<TransactionInput> = match serde_wasm_bindgen::from_value(js_batch) {
+            Ok(i) => i,
+            Err(_) => return "Error: Invalid batch format".to_string(),
+        };
+
+        self.commit_batch(inputs)
+    }
+
     pub fn get_transaction_count(&self) -> usize {
         self.transactions.len()
     }
+
+    /// Returns the current net balance of a single account (0 if never touched).
+    pub fn get_balance(&self, account_id: String) -> i64 {
+        self.balances.get(&account_id).map(Money::minor_units).unwrap_or(0)
+    }
+
+    /// Lists every account that has ever been touched, with its current balance.
+    pub fn list_accounts(&self) -> JsValue {
+        let accounts: Vec<AccountBalance> = self.balances.iter()
+            .map(|(account_id, balance)| AccountBalance { account_id: account_id.clone(), balance: balance.minor_units() })
+            .collect();
+
+        serde_wasm_bindgen::to_value(&accounts).unwrap_or(JsValue::NULL)
+    }
+
+    /// Cheap integrity check: the global sum of all account balances must be zero,
+    /// since every committed `Transaction` is already constrained to sum to zero.
+    /// Balances are rescaled to a common scale (the same way `Transaction::new`
+    /// validates a balanced entry set) rather than summed as raw minor units,
+    /// since different accounts can end up tracked at different scales.
+    pub fn is_balanced(&self) -> bool {
+        let mut amounts = self.balances.values();
+        let mut total = match amounts.next() {
+            Some(amount) => *amount,
+            None => return true,
+        };
+
+        for amount in amounts {
+            total = match total.checked_add(amount) {
+                Ok(t) => t,
+                Err(_) => return false,
+            };
+        }
+
+        total.minor_units() == 0
+    }
+
+    /// Returns an account's dispute-aware state (all zero, unlocked, if the
+    /// account has never been touched).
+    pub fn get_account_state(&self, account_id: String) -> JsValue {
+        let state = self.accounts.get(&account_id).cloned().unwrap_or_default();
+        serde_wasm_bindgen::to_value(&state).unwrap_or(JsValue::NULL)
+    }
+
+    /// Bulk-imports a ledger from CSV text in the common `type, client, tx, amount`
+    /// row format. `deposit`/`withdrawal` rows that share a `tx` id are grouped
+    /// into a single `Transaction` (e.g. a withdrawal leg and a deposit leg
+    /// recording one transfer) and run through the normal `Transaction::new`
+    /// validation, so unbalanced groups are skipped rather than aborting the
+    /// whole import. `dispute`/`resolve`/`chargeback` rows are applied after
+    /// every transaction group has been committed, since they reference a
+    /// `tx` id by its previously committed entries.
+    /// Returns a one-line summary of how many transactions were accepted/rejected.
+    pub fn load_csv(&mut self, data: &str) -> String {
+        let mut groups: HashMap<u32, Vec<Entry>> = HashMap::new();
+        let mut order: Vec<u32> = Vec::new();
+        let mut actions: Vec<(DisputeKind, u32, String)> = Vec::new();
+        let mut unparsable_rows = 0usize;
+
+        for (line_no, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line_no == 0 && line.to_ascii_lowercase().starts_with("type") {
+                continue; // header row
+            }
+
+            match Engine::parse_csv_row(line) {
+                Ok(CsvRow::Entry(tx, entry)) => {
+                    if !groups.contains_key(&tx) {
+                        order.push(tx);
+                    }
+                    groups.entry(tx).or_default().push(entry);
+                }
+                Ok(CsvRow::Action(kind, tx, client)) => actions.push((kind, tx, client)),
+                Err(_) => unparsable_rows += 1,
+            }
+        }
+
+        let mut accepted = 0usize;
+        let mut rejected = unparsable_rows;
+        let mut errors: Vec<String> = Vec::new();
+
+        for tx in order {
+            let entries = groups.remove(&tx).unwrap_or_default();
+            if let Some(locked) = self.locked_account(&entries) {
+                rejected += 1;
+                errors.push(format!("tx {}: account '{}' is locked.", tx, locked));
+                continue;
+            }
+            match Transaction::new(tx, format!("CSV import: tx {}", tx), 0, entries) {
+                Ok(transaction) => {
+                    if let Err(e) = self.apply_balances(&transaction) {
+                        rejected += 1;
+                        errors.push(format!("tx {}: {}", tx, e));
+                        continue;
+                    }
+                    self.transactions.push(transaction);
+                    accepted += 1;
+                }
+                Err(e) => {
+                    rejected += 1;
+                    errors.push(format!("tx {}: {}", tx, e));
+                }
+            }
+        }
+
+        let mut disputes_applied = 0usize;
+        for (kind, tx, client) in actions {
+            if self.apply_dispute_action(kind, tx, &client) {
+                disputes_applied += 1;
+            }
+        }
+
+        if unparsable_rows > 0 {
+            errors.push(format!("{} row(s) could not be parsed.", unparsable_rows));
+        }
+
+        let mut summary = format!("Imported {} transaction(s), {} rejected.", accepted, rejected);
+        if disputes_applied > 0 {
+            summary.push_str(&format!(" Applied {} dispute action(s).", disputes_applied));
+        }
+        if !errors.is_empty() {
+            summary.push_str(&format!(" Errors: {}", errors.join("; ")));
+        }
+        summary
+    }
+}
+
+/// One parsed CSV row: either an `Entry` to fold into a grouped `Transaction`,
+/// or a dispute-lifecycle action to apply once transactions are committed.
+enum CsvRow {
+    Entry(u32, Entry),
+    Action(DisputeKind, u32, String),
+}
+
+impl Engine {
+    /// Folds a committed transaction's entries into the per-account balance
+    /// map and the dispute-aware account state. Balances are merged via
+    /// `Money::checked_add`, which rescales to the finer of the two scales
+    /// involved, so a balance built from mixed-scale entries (e.g. `"1.0"`
+    /// and `"-1.00"`) stays numerically correct instead of summing raw minor
+    /// units across mismatched scales.
+    fn apply_balances(&mut self, tx: &Transaction) -> Result<(), String> {
+        for entry in &tx.entries {
+            Engine::merge_balance(&mut self.balances, entry)?;
+
+            let state = self.accounts.entry(entry.account_id.clone()).or_default();
+            let amount = Engine::prepare_account_delta(state, entry.amount)?;
+            state.available += amount;
+            state.total += amount;
+        }
+        Ok(())
+    }
+
+    /// Merges one entry into a balance map, rescaling via `Money::checked_add`
+    /// when the account already has a balance on record. Factored out so
+    /// `commit_batch` can dry-run a whole batch against a scratch copy of the
+    /// map before mutating real state.
+    fn merge_balance(balances: &mut HashMap<String, Money>, entry: &Entry) -> Result<(), String> {
+        let updated = match balances.get(&entry.account_id) {
+            Some(existing) => existing.checked_add(&entry.amount)?,
+            None => entry.amount,
+        };
+        balances.insert(entry.account_id.clone(), updated);
+        Ok(())
+    }
+
+    /// Rescales `state`'s `available`/`held`/`total` up to `to_scale` in place,
+    /// the same "upscale to the finer scale" strategy `Money::checked_add`
+    /// uses, since the three fields always share one implicit scale (they only
+    /// ever move together by a single rescaled amount).
+    fn rescale_account_state(state: &mut AccountState, to_scale: u32) -> Result<(), String> {
+        if to_scale == state.scale {
+            return Ok(());
+        }
+        state.available = Money::rescale_minor_units(state.available, state.scale, to_scale)?;
+        state.held = Money::rescale_minor_units(state.held, state.scale, to_scale)?;
+        state.total = Money::rescale_minor_units(state.total, state.scale, to_scale)?;
+        state.scale = to_scale;
+        Ok(())
+    }
+
+    /// Rescales `state` (if needed) to the finer of its own scale and
+    /// `amount`'s, then returns `amount` rescaled to that same common scale —
+    /// so the caller can safely `+=`/`-=` the result into `state.available`,
+    /// `state.held` or `state.total` without the scale-mixing bug
+    /// `b832d58` fixed for `balances`.
+    fn prepare_account_delta(state: &mut AccountState, amount: Money) -> Result<i64, String> {
+        let scale = state.scale.max(amount.scale());
+        Engine::rescale_account_state(state, scale)?;
+        Money::rescale_minor_units(amount.minor_units(), amount.scale(), scale)
+    }
+
+    /// Returns the first account referenced by `entries` that is locked, if any.
+    fn locked_account(&self, entries: &[Entry]) -> Option<String> {
+        entries.iter()
+            .map(|entry| &entry.account_id)
+            .find(|account_id| self.accounts.get(*account_id).map(|s| s.locked).unwrap_or(false))
+            .cloned()
+    }
+
+    /// Validates every transaction in the batch (including locked-account
+    /// checks) before applying any of them, so a single failing entry rolls
+    /// the whole batch back instead of leaving it partially committed.
+    fn commit_batch(&mut self, inputs: Vec<TransactionInput>) -> String {
+        if inputs.is_empty() {
+            return "Error: Batch cannot be empty.".to_string();
+        }
+
+        let mut validated = Vec::with_capacity(inputs.len());
+        for (index, input) in inputs.into_iter().enumerate() {
+            let tx = match Transaction::new(input.id, input.description, input.timestamp, input.entries) {
+                Ok(tx) => tx,
+                Err(e) => return format!("Error: Batch rolled back, entry {} failed: {}", index, e),
+            };
+            if let Some(locked) = self.locked_account(&tx.entries) {
+                return format!("Error: Batch rolled back, entry {} failed: account '{}' is locked.", index, locked);
+            }
+            validated.push(tx);
+        }
+
+        // Dry-run the whole batch against scratch copies of the balance map
+        // and account states first: both `merge_balance` and
+        // `prepare_account_delta` can now fail on a currency/scale mismatch
+        // against pre-existing state, and that can't be caught by the checks
+        // above (which only look within each transaction). Catching it here
+        // keeps the "roll back the whole batch" guarantee even for that case.
+        let mut scratch_balances = self.balances.clone();
+        let mut scratch_accounts: HashMap<String, AccountState> = HashMap::new();
+        for (index, tx) in validated.iter().enumerate() {
+            for entry in &tx.entries {
+                if let Err(e) = Engine::merge_balance(&mut scratch_balances, entry) {
+                    return format!("Error: Batch rolled back, entry {} failed: {}", index, e);
+                }
+                let state = scratch_accounts.entry(entry.account_id.clone())
+                    .or_insert_with(|| self.accounts.get(&entry.account_id).cloned().unwrap_or_default());
+                if let Err(e) = Engine::prepare_account_delta(state, entry.amount) {
+                    return format!("Error: Batch rolled back, entry {} failed: {}", index, e);
+                }
+            }
+        }
+
+        let count = validated.len();
+        for tx in validated {
+            self.apply_balances(&tx).expect("validated against scratch balance/account maps above");
+            self.transactions.push(tx);
+        }
+
+        format!("Success: {} transaction(s) committed atomically.", count)
+    }
+
+    /// Looks up the amount a previously committed transaction moved through a
+    /// given account, for disputing. Returns `None` if the transaction or the
+    /// account's entry within it cannot be found.
+    fn disputed_amount(&self, tx: u32, account_id: &str) -> Option<Money> {
+        self.transactions.iter()
+            .find(|t| t.id == tx)
+            .and_then(|t| t.entries.iter().find(|e| e.account_id == account_id))
+            .map(|e| e.amount)
+    }
+
+    /// Applies a `dispute`/`resolve`/`chargeback` action, ignoring it (and
+    /// returning `false`) if it references an unknown transaction/account, one
+    /// that isn't in the right state to accept the action, or (for a fresh
+    /// `Dispute`) a withdrawal leg. Disputes are restricted to deposit legs
+    /// (positive amounts) because holding a negative amount against `held`
+    /// funds is nonsensical; `get_balance` on the disputed account is
+    /// unaffected by a `Dispute`/`Resolve` and only moves on `Chargeback`.
+    fn apply_dispute_action(&mut self, kind: DisputeKind, tx: u32, account_id: &str) -> bool {
+        let amount = match self.disputed_amount(tx, account_id) {
+            Some(amount) => amount,
+            None => return false,
+        };
+        let key = (tx, account_id.to_string());
+
+        match kind {
+            DisputeKind::Dispute => {
+                if amount.minor_units() <= 0 {
+                    return false; // withdrawals can't be held against `held` funds
+                }
+                if self.disputes.contains_key(&key) {
+                    return false; // already disputed, or already settled
+                }
+                let state = self.accounts.entry(account_id.to_string()).or_default();
+                let minor_units = match Engine::prepare_account_delta(state, amount) {
+                    Ok(m) => m,
+                    Err(_) => return false,
+                };
+                self.disputes.insert(key, DisputeStatus::Disputed);
+                let state = self.accounts.get_mut(account_id).unwrap();
+                state.available -= minor_units;
+                state.held += minor_units;
+                true
+            }
+            DisputeKind::Resolve => {
+                if self.disputes.get(&key) != Some(&DisputeStatus::Disputed) {
+                    return false;
+                }
+                let state = self.accounts.entry(account_id.to_string()).or_default();
+                let minor_units = match Engine::prepare_account_delta(state, amount) {
+                    Ok(m) => m,
+                    Err(_) => return false,
+                };
+                self.disputes.remove(&key);
+                let state = self.accounts.get_mut(account_id).unwrap();
+                state.available += minor_units;
+                state.held -= minor_units;
+                true
+            }
+            DisputeKind::Chargeback => {
+                if self.disputes.get(&key) != Some(&DisputeStatus::Disputed) {
+                    return false;
+                }
+                // Reverse the original entry into the contra-account so the
+                // trial balance (`balances`/`is_balanced`) stays reconciled
+                // with `AccountState.total` instead of the funds simply
+                // disappearing from the client's side of the ledger.
+                if Engine::merge_balance(&mut self.balances, &Entry { account_id: account_id.to_string(), amount: amount.negated() }).is_err()
+                    || Engine::merge_balance(&mut self.balances, &Entry { account_id: CHARGEBACK_CONTRA_ACCOUNT.to_string(), amount }).is_err()
+                {
+                    return false;
+                }
+                let state = self.accounts.entry(account_id.to_string()).or_default();
+                let minor_units = match Engine::prepare_account_delta(state, amount) {
+                    Ok(m) => m,
+                    Err(_) => return false,
+                };
+                self.disputes.insert(key, DisputeStatus::ChargedBack);
+                let state = self.accounts.get_mut(account_id).unwrap();
+                state.held -= minor_units;
+                state.total -= minor_units;
+                state.locked = true;
+                true
+            }
+        }
+    }
+
+    /// Parses one `type, client, tx, amount` CSV row into either an `Entry`
+    /// to be grouped into a `Transaction` (`deposit`/`withdrawal`, positive
+    /// for a deposit and negative for a withdrawal) or a dispute-lifecycle
+    /// action (`dispute`/`resolve`/`chargeback`, which ignore the `amount`
+    /// column since it's looked up from the referenced transaction).
+    fn parse_csv_row(line: &str) -> Result<CsvRow, String> {
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        if fields.len() != 4 {
+            return Err(format!("Expected 4 columns, got {}.", fields.len()));
+        }
+        let (kind, client, tx, amount) = (fields[0], fields[1], fields[2], fields[3]);
+        let tx: u32 = tx.parse().map_err(|_| format!("Invalid tx id '{}'.", tx))?;
+
+        match kind.to_ascii_lowercase().as_str() {
+            "deposit" => Ok(CsvRow::Entry(tx, Entry { account_id: client.to_string(), amount: amount.parse()? })),
+            "withdrawal" => Ok(CsvRow::Entry(tx, Entry { account_id: client.to_string(), amount: amount.parse::<Money>()?.negated() })),
+            "dispute" => Ok(CsvRow::Action(DisputeKind::Dispute, tx, client.to_string())),
+            "resolve" => Ok(CsvRow::Action(DisputeKind::Resolve, tx, client.to_string())),
+            "chargeback" => Ok(CsvRow::Action(DisputeKind::Chargeback, tx, client.to_string())),
+            other => Err(format!("Unsupported row type '{}'.", other)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -95,8 +708,8 @@ mod tests {
     #[test]
     fn test_balanced_transaction() {
         let entries = vec![
-            Entry { account_id: "Cash".to_string(), amount: 100 },
-            Entry { account_id: "Revenue".to_string(), amount: -100 },
+            Entry { account_id: "Cash".to_string(), amount: Money::new(100, 2, "USD").unwrap() },
+            Entry { account_id: "Revenue".to_string(), amount: Money::new(-100, 2, "USD").unwrap() },
         ];
         let tx = Transaction::new(1, "Sale".to_string(), 1000, entries);
         assert!(tx.is_ok());
@@ -105,10 +718,261 @@ mod tests {
     #[test]
     fn test_unbalanced_transaction() {
         let entries = vec![
-            Entry { account_id: "Cash".to_string(), amount: 100 },
-            Entry { account_id: "Revenue".to_string(), amount: -50 },
+            Entry { account_id: "Cash".to_string(), amount: Money::new(100, 2, "USD").unwrap() },
+            Entry { account_id: "Revenue".to_string(), amount: Money::new(-50, 2, "USD").unwrap() },
         ];
         let tx = Transaction::new(2, "Bad Math".to_string(), 1001, entries);
         assert!(tx.is_err());
     }
+
+    #[test]
+    fn test_mixed_currency_transaction_is_rejected() {
+        let entries = vec![
+            Entry { account_id: "Cash".to_string(), amount: Money::new(100, 2, "USD").unwrap() },
+            Entry { account_id: "Revenue".to_string(), amount: Money::new(-100, 2, "EUR").unwrap() },
+        ];
+        let tx = Transaction::new(3, "Bad Currency".to_string(), 1002, entries);
+        assert!(tx.is_err());
+    }
+
+    #[test]
+    fn test_money_from_str_round_trips() {
+        let money: Money = "100.00".parse().unwrap();
+        assert_eq!(money.minor_units(), 10000);
+        assert_eq!(money.scale(), 2);
+        assert_eq!(money.to_string(), "100.00");
+
+        let negative: Money = "-1.5".parse().unwrap();
+        assert_eq!(negative.to_string(), "-1.5");
+    }
+
+    #[test]
+    fn test_balances_track_per_account_and_stay_balanced() {
+        let mut engine = Engine::new();
+
+        let tx = Transaction::new(1, "Sale".to_string(), 1000, vec![
+            Entry { account_id: "Cash".to_string(), amount: Money::new(100, 2, "USD").unwrap() },
+            Entry { account_id: "Revenue".to_string(), amount: Money::new(-100, 2, "USD").unwrap() },
+        ]).unwrap();
+        engine.apply_balances(&tx).unwrap();
+
+        assert_eq!(engine.get_balance("Cash".to_string()), 100);
+        assert_eq!(engine.get_balance("Revenue".to_string()), -100);
+        assert_eq!(engine.get_balance("Unknown".to_string()), 0);
+        assert!(engine.is_balanced());
+    }
+
+    #[test]
+    fn test_balances_stay_reconciled_across_mixed_scales() {
+        let mut engine = Engine::new();
+
+        let first = Transaction::new(1, "Deposit".to_string(), 1000, vec![
+            Entry { account_id: "Cash".to_string(), amount: "1.00".parse().unwrap() },
+            Entry { account_id: "Revenue".to_string(), amount: "-1.00".parse().unwrap() },
+        ]).unwrap();
+        engine.apply_balances(&first).unwrap();
+
+        // Same amount, coarser scale (tenths instead of hundredths) — merging
+        // this must rescale rather than summing raw minor units, or the two
+        // equal-but-differently-scaled legs would net to nonzero.
+        let second = Transaction::new(2, "Reversal".to_string(), 1001, vec![
+            Entry { account_id: "Cash".to_string(), amount: "-1.0".parse().unwrap() },
+            Entry { account_id: "Revenue".to_string(), amount: "1.0".parse().unwrap() },
+        ]).unwrap();
+        engine.apply_balances(&second).unwrap();
+
+        assert_eq!(engine.get_balance("Cash".to_string()), 0);
+        assert_eq!(engine.get_balance("Revenue".to_string()), 0);
+        assert!(engine.is_balanced());
+    }
+
+    #[test]
+    fn test_account_state_stays_reconciled_across_mixed_scales() {
+        let mut engine = Engine::new();
+        engine.load_csv(
+            "type, client, tx, amount\n\
+             deposit, Alice, 1, 50\n\
+             withdrawal, Bob, 1, 50\n\
+             deposit, Alice, 2, 50.00\n\
+             withdrawal, Bob, 2, 50.00\n"
+        );
+
+        // "50" (scale 0) and "50.00" (scale 2) are the same amount; the
+        // account-state accumulator must rescale to match `balances` instead
+        // of summing raw minor units (which would wrongly read 5050).
+        let alice = engine.accounts.get("Alice").unwrap();
+        assert_eq!(alice.available, 10000);
+        assert_eq!(alice.total, 10000);
+        assert_eq!(engine.get_balance("Alice".to_string()), 10000);
+    }
+
+    #[test]
+    fn test_load_csv_groups_rows_by_tx_and_commits_balanced_transfers() {
+        let mut engine = Engine::new();
+
+        let summary = engine.load_csv(
+            "type, client, tx, amount\n\
+             withdrawal, Checking, 1, 50.00\n\
+             deposit, Savings, 1, 50.00\n\
+             deposit, Checking, 2, 10.00\n"
+        );
+
+        assert_eq!(engine.get_transaction_count(), 1);
+        assert_eq!(engine.get_balance("Checking".to_string()), -5000);
+        assert_eq!(engine.get_balance("Savings".to_string()), 5000);
+        assert!(summary.contains("Imported 1 transaction(s), 1 rejected."));
+    }
+
+    #[test]
+    fn test_load_csv_rejects_unparsable_rows() {
+        let mut engine = Engine::new();
+
+        let summary = engine.load_csv("type, client, tx, amount\nnotarow\n");
+
+        assert_eq!(engine.get_transaction_count(), 0);
+        assert!(summary.contains("Imported 0 transaction(s), 1 rejected."));
+    }
+
+    #[test]
+    fn test_dispute_then_resolve_moves_funds_between_available_and_held() {
+        let mut engine = Engine::new();
+        engine.load_csv(
+            "type, client, tx, amount\n\
+             deposit, Alice, 1, 50.00\n\
+             withdrawal, Bob, 1, 50.00\n"
+        );
+        assert_eq!(engine.accounts.get("Alice").unwrap().available, 5000);
+
+        engine.load_csv("type, client, tx, amount\ndispute, Alice, 1, \n");
+        let alice = engine.accounts.get("Alice").unwrap();
+        assert_eq!(alice.available, 0);
+        assert_eq!(alice.held, 5000);
+        assert_eq!(alice.total, 5000);
+        assert!(!alice.locked);
+
+        engine.load_csv("type, client, tx, amount\nresolve, Alice, 1, \n");
+        let alice = engine.accounts.get("Alice").unwrap();
+        assert_eq!(alice.available, 5000);
+        assert_eq!(alice.held, 0);
+    }
+
+    #[test]
+    fn test_chargeback_locks_account_and_blocks_future_entries() {
+        let mut engine = Engine::new();
+        engine.load_csv(
+            "type, client, tx, amount\n\
+             deposit, Alice, 1, 50.00\n\
+             withdrawal, Bob, 1, 50.00\n"
+        );
+        engine.load_csv("type, client, tx, amount\ndispute, Alice, 1, \nchargeback, Alice, 1, \n");
+
+        let alice = engine.accounts.get("Alice").unwrap();
+        assert_eq!(alice.held, 0);
+        assert_eq!(alice.total, 0);
+        assert!(alice.locked);
+
+        let summary = engine.load_csv(
+            "type, client, tx, amount\n\
+             deposit, Alice, 2, 10.00\n\
+             withdrawal, Bob, 2, 10.00\n"
+        );
+        assert_eq!(engine.get_transaction_count(), 1);
+        assert!(summary.contains("is locked"));
+    }
+
+    #[test]
+    fn test_dispute_referencing_unknown_transaction_is_ignored() {
+        let mut engine = Engine::new();
+        let summary = engine.load_csv("type, client, tx, amount\ndispute, Alice, 999, \n");
+        assert!(!summary.contains("dispute action"));
+        assert!(!engine.accounts.contains_key("Alice"));
+    }
+
+    #[test]
+    fn test_dispute_on_withdrawal_leg_is_ignored() {
+        let mut engine = Engine::new();
+        engine.load_csv(
+            "type, client, tx, amount\n\
+             deposit, Alice, 1, 50.00\n\
+             withdrawal, Bob, 1, 50.00\n"
+        );
+
+        engine.load_csv("type, client, tx, amount\ndispute, Bob, 1, \n");
+        let bob = engine.accounts.get("Bob").unwrap();
+        assert_eq!(bob.available, -5000);
+        assert_eq!(bob.held, 0);
+    }
+
+    #[test]
+    fn test_chargeback_reverses_balance_into_contra_account() {
+        let mut engine = Engine::new();
+        engine.load_csv(
+            "type, client, tx, amount\n\
+             deposit, Alice, 1, 50.00\n\
+             withdrawal, Bob, 1, 50.00\n"
+        );
+        assert!(engine.is_balanced());
+
+        engine.load_csv("type, client, tx, amount\ndispute, Alice, 1, \nchargeback, Alice, 1, \n");
+
+        let alice = engine.accounts.get("Alice").unwrap();
+        assert_eq!(engine.get_balance("Alice".to_string()), 0);
+        assert_eq!(alice.total, 0);
+        assert_eq!(engine.get_balance("Chargebacks".to_string()), 5000);
+        assert!(engine.is_balanced());
+    }
+
+    #[test]
+    fn test_commit_batch_applies_all_transactions_together() {
+        let mut engine = Engine::new();
+
+        let summary = engine.commit_batch(vec![
+            TransactionInput {
+                id: 1, description: "Payroll: Alice".to_string(), timestamp: 1000,
+                entries: vec![
+                    Entry { account_id: "Payroll".to_string(), amount: Money::new(-500, 2, "USD").unwrap() },
+                    Entry { account_id: "Alice".to_string(), amount: Money::new(500, 2, "USD").unwrap() },
+                ],
+            },
+            TransactionInput {
+                id: 2, description: "Payroll: Bob".to_string(), timestamp: 1000,
+                entries: vec![
+                    Entry { account_id: "Payroll".to_string(), amount: Money::new(-300, 2, "USD").unwrap() },
+                    Entry { account_id: "Bob".to_string(), amount: Money::new(300, 2, "USD").unwrap() },
+                ],
+            },
+        ]);
+
+        assert!(summary.contains("Success: 2 transaction(s)"));
+        assert_eq!(engine.get_transaction_count(), 2);
+        assert_eq!(engine.get_balance("Payroll".to_string()), -800);
+        assert_eq!(engine.get_balance("Alice".to_string()), 500);
+        assert_eq!(engine.get_balance("Bob".to_string()), 300);
+    }
+
+    #[test]
+    fn test_commit_batch_rolls_back_entirely_on_a_single_unbalanced_entry() {
+        let mut engine = Engine::new();
+
+        let summary = engine.commit_batch(vec![
+            TransactionInput {
+                id: 1, description: "Good".to_string(), timestamp: 1000,
+                entries: vec![
+                    Entry { account_id: "Payroll".to_string(), amount: Money::new(-500, 2, "USD").unwrap() },
+                    Entry { account_id: "Alice".to_string(), amount: Money::new(500, 2, "USD").unwrap() },
+                ],
+            },
+            TransactionInput {
+                id: 2, description: "Bad".to_string(), timestamp: 1000,
+                entries: vec![
+                    Entry { account_id: "Payroll".to_string(), amount: Money::new(-300, 2, "USD").unwrap() },
+                    Entry { account_id: "Bob".to_string(), amount: Money::new(250, 2, "USD").unwrap() },
+                ],
+            },
+        ]);
+
+        assert!(summary.contains("entry 1 failed"));
+        assert_eq!(engine.get_transaction_count(), 0);
+        assert_eq!(engine.get_balance("Payroll".to_string()), 0);
+    }
 }